@@ -0,0 +1,52 @@
+use std::{fs, io, path::Path};
+
+/// The raw text of a config file, read either into an owned `String` or,
+/// with the `mmap` feature, mapped read-only straight from disk. Recursive
+/// `import`s can pull in many files for one logical config, so avoiding a
+/// heap copy per file matters for large, heavily-imported trees.
+pub enum ConfigContents {
+    Owned(String),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl ConfigContents {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ConfigContents::Owned(s) => s,
+            // Validated UTF-8 in `read` before this variant is ever constructed.
+            #[cfg(feature = "mmap")]
+            ConfigContents::Mapped(mmap) => {
+                std::str::from_utf8(mmap).expect("mmap validated as UTF-8 on read")
+            }
+        }
+    }
+}
+
+/// Read a config file's contents, preferring a read-only mmap when the
+/// `mmap` feature is enabled. Falls back to `fs::read_to_string` if the file
+/// can't be mapped (e.g. it isn't a regular file) or isn't valid UTF-8.
+pub fn read(path: &Path) -> Result<ConfigContents, io::Error> {
+    #[cfg(feature = "mmap")]
+    {
+        if let Ok(file) = fs::File::open(path) {
+            // SAFETY: `Mmap::map` is unsafe because the mapping becomes
+            // invalid if `file` is truncated by another process while it's
+            // mapped, turning further reads (here, `from_utf8`/`as_str`)
+            // into UB (typically a SIGBUS, not a catchable Rust panic). A
+            // hotkey daemon reloading on every config edit makes that a
+            // real, not theoretical, race. This is an accepted risk of the
+            // optional `mmap` feature: it trades that risk for avoiding a
+            // full read on every reload of a large, heavily-imported
+            // config. Callers who can't accept it should not enable the
+            // feature; `ConfigContents::Owned` via `fs::read_to_string`
+            // has no such risk.
+            if let Ok(mmap) = unsafe { memmap2::Mmap::map(&file) } {
+                if std::str::from_utf8(&mmap).is_ok() {
+                    return Ok(ConfigContents::Mapped(mmap));
+                }
+            }
+        }
+    }
+    Ok(ConfigContents::Owned(fs::read_to_string(path)?))
+}
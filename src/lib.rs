@@ -2,11 +2,17 @@ use itertools::Itertools;
 use pest::{iterators::Pair, Parser};
 use pest_derive::Parser;
 use range::Bounds;
-use std::{collections::BTreeSet, fmt::Display, fs, path::Path};
+use std::{collections::BTreeSet, fmt::Display, path::Path, sync::Arc};
 use thiserror::Error;
+mod config;
 mod range;
+mod span;
 pub mod token;
+mod trie;
+use crate::config::ConfigContents;
 use crate::token::{Key, KeyAttribute, Modifier};
+pub use span::Span;
+pub use trie::{BindingTrie, Conflict, ConflictEntry};
 
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -18,6 +24,13 @@ pub enum ParseError {
     MainSection,
     #[error("unable to read config file")]
     ReadingConfig(#[from] std::io::Error),
+    #[error("error while importing `{path}`")]
+    Import {
+        path: String,
+        source: Box<ParseError>,
+    },
+    #[error(transparent)]
+    Conflict(#[from] Box<Conflict>),
 }
 
 #[derive(Parser)]
@@ -31,6 +44,7 @@ pub struct Mode {
     pub swallow: bool,
     pub bindings: Vec<Binding>,
     pub unbinds: Vec<Definition>,
+    pub span: Span,
 }
 
 pub struct SwhkdParser {
@@ -54,18 +68,65 @@ impl SwhkdParser {
         root.imports = root_imports;
         Ok(root)
     }
-    fn as_import(input: ParserInput, seen: &mut BTreeSet<String>) -> Result<Self, ParseError> {
+
+    /// Like [`SwhkdParser::from`], but additionally fails on the first
+    /// [`Conflict`] between two bindings, e.g. two rules compiling to the
+    /// same modifier set + key.
+    pub fn from_checked(input: ParserInput) -> Result<Self, ParseError> {
+        let parsed = Self::from(input)?;
+        if let Some(conflict) = parsed.check_conflicts().into_iter().next() {
+            return Err(ParseError::Conflict(Box::new(conflict)));
+        }
+        Ok(parsed)
+    }
+
+    /// Detect bindings that compile to the same chord and would silently
+    /// shadow one another. Conflicts are checked within the top-level
+    /// bindings and within each mode's bindings independently, since a mode
+    /// is its own binding scope.
+    pub fn check_conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = vec![];
+        conflicts.extend(Self::conflicts_in(&self.bindings));
+        for mode in &self.modes {
+            conflicts.extend(Self::conflicts_in(&mode.bindings));
+        }
+        conflicts
+    }
+
+    fn conflicts_in(bindings: &[Binding]) -> Vec<Conflict> {
+        let mut trie = BindingTrie::new();
+        bindings
+            .iter()
+            .filter_map(|binding| trie.insert(binding).err())
+            .collect()
+    }
+
+    /// Read a config file (or take a raw string) and hand back its contents
+    /// together with the source path recorded in spans and diagnostics.
+    /// Grammar parsing of those contents is done by `main_section`. Both are
+    /// shared by [`SwhkdParser::as_import`] and
+    /// [`SwhkdParser::as_import_recovering`] so the two entry points can't
+    /// drift on how a single file is read and parsed.
+    fn parse_one_file(input: ParserInput) -> Result<(ConfigContents, Arc<str>), ParseError> {
         let (raw, source) = match input {
-            ParserInput::Raw(s) => (s.to_string(), "<anonymous>"),
-            // TODO: Use mmap instead of fs::read_to_string
-            ParserInput::Path(p) => (fs::read_to_string(p)?, p.to_str().unwrap_or_default()),
+            ParserInput::Raw(s) => (ConfigContents::Owned(s.to_string()), "<anonymous>"),
+            ParserInput::Path(p) => (config::read(p)?, p.to_str().unwrap_or_default()),
         };
-        let parse_result = SwhkdGrammar::parse(Rule::main, &raw)
+        Ok((raw, Arc::from(source)))
+    }
+
+    fn main_section<'r>(
+        raw: &'r ConfigContents,
+        source: &Arc<str>,
+    ) -> Result<Pair<'r, Rule>, ParseError> {
+        let parse_result = SwhkdGrammar::parse(Rule::main, raw.as_str())
             .map_err(|err| ParseError::Grammar(Box::new(err.with_path(source))))?;
+        parse_result.into_iter().next().ok_or(ParseError::MainSection)
+    }
 
-        let Some(contents) = parse_result.into_iter().next() else {
-            return Err(ParseError::MainSection);
-        };
+    fn as_import(input: ParserInput, seen: &mut BTreeSet<String>) -> Result<Self, ParseError> {
+        let (raw, source) = Self::parse_one_file(input)?;
+        let contents = Self::main_section(&raw, &source)?;
 
         let mut bindings = vec![];
         let mut unbinds = vec![];
@@ -73,9 +134,9 @@ impl SwhkdParser {
         let mut modes = vec![];
         for decl in contents.into_inner() {
             match decl.as_rule() {
-                Rule::binding => bindings.extend(binding_parser(decl)?),
-                Rule::unbind => unbinds.extend(unbind_parser(decl)?),
-                Rule::mode => modes.push(mode_parser(decl)?),
+                Rule::binding => bindings.extend(binding_parser(decl, source.clone())?),
+                Rule::unbind => unbinds.extend(unbind_parser(decl, source.clone())?),
+                Rule::mode => modes.push(mode_parser(decl, source.clone())?),
                 Rule::import => imports.extend(import_parser(decl)),
                 // End of identifier
                 // Here, it means the end of the file.
@@ -101,15 +162,108 @@ impl SwhkdParser {
             modes,
         })
     }
+
+    /// Like [`SwhkdParser::from`], but keeps going past recoverable mistakes
+    /// instead of bailing on the first one. Returns the best-effort parse
+    /// result alongside every [`ParseError`] collected along the way, so a
+    /// caller can print all of them in one go rather than fix-and-rerun.
+    ///
+    /// Only a config whose grammar fails to parse outright (or whose file
+    /// can't be read at all) yields `None`; a bad range, a mismatched
+    /// binding/command count, or a broken import are all recorded as errors
+    /// while the rest of the declarations are still parsed.
+    pub fn parse_with_errors(input: ParserInput) -> (Option<Self>, Vec<ParseError>) {
+        let mut root_imports = BTreeSet::new();
+        let (parsed, errors) = Self::as_import_recovering(input, &mut root_imports);
+        let parsed = parsed.map(|mut root| {
+            root.imports = root_imports;
+            root
+        });
+        (parsed, errors)
+    }
+
+    fn as_import_recovering(
+        input: ParserInput,
+        seen: &mut BTreeSet<String>,
+    ) -> (Option<Self>, Vec<ParseError>) {
+        let mut errors = vec![];
+        let (raw, source) = match Self::parse_one_file(input) {
+            Ok(pair) => pair,
+            Err(err) => {
+                errors.push(err);
+                return (None, errors);
+            }
+        };
+        let contents = match Self::main_section(&raw, &source) {
+            Ok(contents) => contents,
+            Err(err) => {
+                errors.push(err);
+                return (None, errors);
+            }
+        };
+
+        let mut bindings = vec![];
+        let mut unbinds = vec![];
+        let mut imports = BTreeSet::new();
+        let mut modes = vec![];
+        for decl in contents.into_inner() {
+            match decl.as_rule() {
+                Rule::binding => match binding_parser(decl, source.clone()) {
+                    Ok(parsed) => bindings.extend(parsed),
+                    Err(err) => errors.push(err),
+                },
+                Rule::unbind => match unbind_parser(decl, source.clone()) {
+                    Ok(parsed) => unbinds.extend(parsed),
+                    Err(err) => errors.push(err),
+                },
+                Rule::mode => match mode_parser(decl, source.clone()) {
+                    Ok(parsed) => modes.push(parsed),
+                    Err(err) => errors.push(err),
+                },
+                Rule::import => imports.extend(import_parser(decl)),
+                // End of input
+                // Here, it means the end of the file.
+                Rule::EOI => {}
+                _ => unreachable!(),
+            }
+        }
+
+        while let Some(import) = imports.pop_first() {
+            if !seen.insert(import.clone()) {
+                continue;
+            }
+            let (child, child_errors) =
+                Self::as_import_recovering(ParserInput::Path(Path::new(&import)), seen);
+            errors.extend(child_errors.into_iter().map(|err| ParseError::Import {
+                path: import.clone(),
+                source: Box::new(err),
+            }));
+            if let Some(child) = child {
+                imports.extend(child.imports);
+                bindings.extend(child.bindings);
+                unbinds.extend(child.unbinds);
+                modes.extend(child.modes);
+            }
+        }
+        (
+            Some(SwhkdParser {
+                bindings,
+                unbinds,
+                imports,
+                modes,
+            }),
+            errors,
+        )
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct Definition {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
     pub modifiers: Vec<Modifier>,
     pub key: Key,
 }
 
-impl Display for Definition {
+impl Display for Chord {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
         for modifier in self.modifiers.iter() {
@@ -120,10 +274,34 @@ impl Display for Definition {
     }
 }
 
+/// A binding's trigger: an ordered, non-empty sequence of one or more
+/// [`Chord`]s. A plain `super + a` is a sequence of length one; an
+/// emacs/tmux-style prefix binding like `super + a ; b ; c` is a sequence of
+/// three, fired by pressing each chord in turn.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Definition {
+    pub chords: Vec<Chord>,
+    pub span: Span,
+}
+
+impl Display for Definition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut chords = self.chords.iter();
+        if let Some(first) = chords.next() {
+            write!(f, "{first}")?;
+        }
+        for chord in chords {
+            write!(f, " ; {chord}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Binding {
     pub definition: Definition,
     pub command: String,
+    pub span: Span,
 }
 impl Display for Binding {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -199,12 +377,12 @@ impl DefinitionUncompiled {
         Ok(())
     }
 
-    fn compile(self) -> Vec<Definition> {
+    fn compile(self) -> Vec<Chord> {
         if self.modifiers.is_empty() {
             return self
                 .keys
                 .into_iter()
-                .map(|key| Definition {
+                .map(|key| Chord {
                     modifiers: vec![],
                     key,
                 })
@@ -214,17 +392,30 @@ impl DefinitionUncompiled {
             .into_iter()
             .multi_cartesian_product()
             .cartesian_product(self.keys)
-            .map(|(modifiers, key)| Definition { modifiers, key })
+            .map(|(modifiers, key)| Chord { modifiers, key })
             .collect()
     }
 }
 
-fn unbind_parser(pair: Pair<'_, Rule>) -> Result<Vec<Definition>, ParseError> {
+fn unbind_parser(pair: Pair<'_, Rule>, path: Arc<str>) -> Result<Vec<Definition>, ParseError> {
+    let span = Span::new(&pair, path);
     let mut uncompiled = DefinitionUncompiled::default();
-    for thing in pair.into_inner() {
-        uncompiled.ingest(thing)?;
+    // `template.pest`'s `unbind` rule wraps its one chord the same way
+    // `binding` does, but unbinds don't support sequences, so there's
+    // exactly one `chord` pair to descend into.
+    for chord in pair.into_inner() {
+        for thing in chord.into_inner() {
+            uncompiled.ingest(thing)?;
+        }
     }
-    Ok(uncompiled.compile())
+    Ok(uncompiled
+        .compile()
+        .into_iter()
+        .map(|chord| Definition {
+            chords: vec![chord],
+            span: span.clone(),
+        })
+        .collect())
 }
 
 fn import_parser(pair: Pair<'_, Rule>) -> Vec<String> {
@@ -249,13 +440,20 @@ fn parse_command_shorthand(pair: Pair<'_, Rule>) -> Result<Vec<String>, ParseErr
     }
     Ok(command_variants)
 }
-fn mode_parser(pair: Pair<'_, Rule>) -> Result<Mode, ParseError> {
-    let mut mode = Mode::default();
+fn mode_parser(pair: Pair<'_, Rule>, path: Arc<str>) -> Result<Mode, ParseError> {
+    let mut mode = Mode {
+        span: Span::new(&pair, path.clone()),
+        ..Default::default()
+    };
     for component in pair.into_inner() {
         match component.as_rule() {
             Rule::modename => mode.name = component.as_str().to_string(),
-            Rule::binding => mode.bindings.extend(binding_parser(component)?),
-            Rule::unbind => mode.unbinds.extend(unbind_parser(component)?),
+            Rule::binding => mode
+                .bindings
+                .extend(binding_parser(component, path.clone())?),
+            Rule::unbind => mode
+                .unbinds
+                .extend(unbind_parser(component, path.clone())?),
             Rule::oneoff => mode.oneoff = true,
             Rule::swallow => mode.swallow = true,
             _ => {}
@@ -264,9 +462,15 @@ fn mode_parser(pair: Pair<'_, Rule>) -> Result<Mode, ParseError> {
     Ok(mode)
 }
 
-fn binding_parser(pair: Pair<'_, Rule>) -> Result<Vec<Binding>, ParseError> {
+fn binding_parser(pair: Pair<'_, Rule>, path: Arc<str>) -> Result<Vec<Binding>, ParseError> {
+    let span = Span::new(&pair, path);
     let mut comm = vec![];
-    let mut uncompiled = DefinitionUncompiled::default();
+    // `template.pest`'s `binding` rule groups each chord of a `;`-separated
+    // sequence (e.g. `super + a ; b ; c`) under its own `chord` pair; a
+    // plain, single-chord binding is just a sequence of length one. Each
+    // entry here is the set of shorthand-expanded `Chord` variants for one
+    // position in the sequence.
+    let mut chord_variants: Vec<Vec<Chord>> = vec![];
     for component in pair.clone().into_inner() {
         match component.as_rule() {
             Rule::command => {
@@ -282,10 +486,20 @@ fn binding_parser(pair: Pair<'_, Rule>) -> Result<Vec<Binding>, ParseError> {
                     }
                 }
             }
-            _ => uncompiled.ingest(component)?,
+            Rule::chord => {
+                let mut uncompiled = DefinitionUncompiled::default();
+                for inner in component.into_inner() {
+                    uncompiled.ingest(inner)?;
+                }
+                chord_variants.push(uncompiled.compile());
+            }
+            _ => unreachable!(),
         }
     }
-    let bind_cartesian_product = uncompiled.compile();
+    let bind_cartesian_product: Vec<Vec<Chord>> = chord_variants
+        .into_iter()
+        .multi_cartesian_product()
+        .collect();
     let command_cartesian_product = comm
         .into_iter()
         .multi_cartesian_product()
@@ -310,9 +524,13 @@ fn binding_parser(pair: Pair<'_, Rule>) -> Result<Vec<Binding>, ParseError> {
     let bindings = bind_cartesian_product
         .into_iter()
         .zip(command_cartesian_product)
-        .map(|(definition, command)| Binding {
-            definition,
+        .map(|(chords, command)| Binding {
+            definition: Definition {
+                chords,
+                span: span.clone(),
+            },
             command,
+            span: span.clone(),
         })
         .collect();
     Ok(bindings)
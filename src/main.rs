@@ -30,7 +30,7 @@ fn extract_trigger(component: Pair<'_, Rule>) -> Vec<Token> {
             let mut keys = vec![];
             for shorthand_component in component.into_inner() {
                 match shorthand_component.as_rule() {
-                    Rule::keybind => {
+                    Rule::key_in_shorthand => {
                         keys.push(Token::Key(shorthand_component.as_str().to_string()));
                     }
                     Rule::key_range => {
@@ -44,7 +44,7 @@ fn extract_trigger(component: Pair<'_, Rule>) -> Vec<Token> {
             }
             keys
         }
-        Rule::keybind => {
+        Rule::key_normal => {
             vec![Token::Key(component.as_str().to_string())]
         }
 
@@ -54,8 +54,10 @@ fn extract_trigger(component: Pair<'_, Rule>) -> Vec<Token> {
 
 fn unbind_parser(pair: Pair<'_, Rule>) {
     let mut unbind = vec![];
-    for component in pair.into_inner() {
-        unbind.push(extract_trigger(component));
+    for chord in pair.into_inner() {
+        for component in chord.into_inner() {
+            unbind.push(extract_trigger(component));
+        }
     }
     let unbind_cartesian_product: Vec<_> = unbind.iter().multi_cartesian_product().collect();
     for trigger_to_unbind in unbind_cartesian_product {
@@ -112,22 +114,28 @@ fn binding_parser(pair: Pair<'_, Rule>) {
             Rule::command => {
                 for subcomponent in component.into_inner() {
                     match subcomponent.as_rule() {
-                        Rule::command_component => {
+                        Rule::command_standalone => {
                             comm.push(vec![Token::Command(subcomponent.as_str().to_string())]);
                         }
-                        Rule::command_with_brace => {
+                        Rule::command_shorthand => {
                             comm.push(parse_command_shorthand(subcomponent));
                         }
                         _ => {}
                     }
                 }
             }
-            _ => {
-                let trigger = extract_trigger(component);
-                if !trigger.is_empty() {
-                    tokens.push(trigger);
+            // `binding` wraps each chord of a `;`-separated sequence in its
+            // own `chord` pair; this standalone parser doesn't model
+            // sequences, so its chords are just flattened together.
+            Rule::chord => {
+                for trigger_component in component.into_inner() {
+                    let trigger = extract_trigger(trigger_component);
+                    if !trigger.is_empty() {
+                        tokens.push(trigger);
+                    }
                 }
             }
+            _ => {}
         }
     }
     let bind_cartesian_product: Vec<_> = tokens.iter().multi_cartesian_product().collect();
@@ -142,7 +150,7 @@ fn binding_parser(pair: Pair<'_, Rule>) {
 
     let composition: Vec<_> = bind_cartesian_product
         .into_iter()
-        .zip(command_cartesian_product.into_iter())
+        .zip(command_cartesian_product)
         .collect();
 
     for (binding, command) in composition {
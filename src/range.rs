@@ -0,0 +1,58 @@
+use pest::iterators::Pair;
+
+use crate::{ParseError, Rule};
+
+/// A `{lower-upper}` shorthand range, e.g. `{1-9}` or `{a-c}`, shared by key
+/// shorthands and command shorthands. Both grammar rules it wraps
+/// (`key_range`, `range`) have the same shape: two `bound`s separated by `-`.
+/// Each bound must be a single character; an escaped bound such as `\-` is
+/// reported as an error.
+pub struct Bounds<'a> {
+    pair: Pair<'a, Rule>,
+}
+
+impl<'a> Bounds<'a> {
+    pub fn new(pair: Pair<'a, Rule>) -> Self {
+        Bounds { pair }
+    }
+
+    fn bound(bound: Pair<'_, Rule>) -> Result<char, ParseError> {
+        bound.as_str().parse::<char>().map_err(|_| {
+            let err = pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::<Rule>::CustomError {
+                    message: "a range bound must be a single character".to_string(),
+                },
+                bound.as_span(),
+            );
+            ParseError::Grammar(Box::new(err))
+        })
+    }
+
+    fn bounds(&self) -> Result<(char, char), ParseError> {
+        let mut inner = self.pair.clone().into_inner();
+        let lower = inner.next().expect("a range always has a lower bound");
+        let upper = inner.next().expect("a range always has an upper bound");
+        let lower = Self::bound(lower)?;
+        let upper = Self::bound(upper)?;
+        if lower > upper {
+            let err = pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::<Rule>::CustomError {
+                    message: format!(
+                        "range lower bound `{lower}` must not be greater than upper bound `{upper}`"
+                    ),
+                },
+                self.pair.as_span(),
+            );
+            return Err(ParseError::Grammar(Box::new(err)));
+        }
+        Ok((lower, upper))
+    }
+
+    pub fn expand_keys(&self) -> Result<(char, char), ParseError> {
+        self.bounds()
+    }
+
+    pub fn expand_commands(&self) -> Result<(char, char), ParseError> {
+        self.bounds()
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use pest::iterators::Pair;
+
+use crate::Rule;
+
+/// A byte range into a source file, used to point diagnostics at the exact
+/// location a `Binding`, `Definition`, or `Mode` came from.
+///
+/// `path` is cheaply cloned (`Arc<str>`) since every node parsed out of the
+/// same file or import shares the same originating path.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub path: Arc<str>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Span {
+            path: Arc::from(""),
+            start: 0,
+            end: 0,
+        }
+    }
+}
+
+impl Span {
+    pub(crate) fn new(pair: &Pair<'_, Rule>, path: Arc<str>) -> Self {
+        let span = pair.as_span();
+        Span {
+            path,
+            start: span.start(),
+            end: span.end(),
+        }
+    }
+}
+
+// Spans record provenance, not meaning: two otherwise identical bindings
+// parsed from different places (or the same shorthand expanded twice) should
+// still compare equal, so equality always holds here and the containing
+// types derive `PartialEq` across their semantic fields only.
+impl PartialEq for Span {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+impl Eq for Span {}
@@ -0,0 +1,21 @@
+//! The leaf tokens a chord is built from: modifiers and keys.
+
+bitflags::bitflags! {
+    /// Flags carried by a key, set by the `@` (send) and `~` (on-release)
+    /// grammar prefixes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct KeyAttribute: u8 {
+        const None = 0;
+        const Send = 1 << 0;
+        const OnRelease = 1 << 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modifier(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub key: String,
+    pub attribute: KeyAttribute,
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::{Binding, Chord, Span};
+
+/// The canonical, comparable form of a `Chord`'s modifiers and key, used as
+/// an edge key in the trie so that e.g. `shift + super` and `super + shift`
+/// collide with each other.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ChordKey {
+    modifiers: Vec<String>,
+    key: String,
+    attribute: String,
+}
+
+impl ChordKey {
+    fn from_chord(chord: &Chord) -> Self {
+        let mut modifiers: Vec<String> = chord
+            .modifiers
+            .iter()
+            .map(|modifier| format!("{modifier:?}"))
+            .collect();
+        modifiers.sort();
+        modifiers.dedup();
+        ChordKey {
+            modifiers,
+            key: chord.key.key.clone(),
+            attribute: format!("{:?}", chord.key.attribute),
+        }
+    }
+}
+
+/// Enough context about a binding on either side of a [`Conflict`] to point
+/// a user at it.
+#[derive(Debug, Clone)]
+pub struct ConflictEntry {
+    pub command: String,
+    pub span: Span,
+}
+
+impl From<&Binding> for ConflictEntry {
+    fn from(binding: &Binding) -> Self {
+        ConflictEntry {
+            command: binding.command.clone(),
+            span: binding.span.clone(),
+        }
+    }
+}
+
+/// A reported clash between two bindings in the same [`BindingTrie`].
+#[derive(Debug, Error)]
+pub enum Conflict {
+    /// Two bindings compile to the exact same chord sequence.
+    #[error("key is already bound to `{}`, cannot also bind it to `{}`", existing.command, incoming.command)]
+    KeyAlreadySet {
+        existing: ConflictEntry,
+        incoming: ConflictEntry,
+    },
+    /// One binding's chord sequence is a strict prefix of another's, so the
+    /// longer one can never fire: whichever of the two is pressed first
+    /// triggers the shorter binding before the rest of the sequence is read.
+    #[error("binding `{}` is a prefix of `{}`'s chord sequence, so `{}` can never fire", existing.command, incoming.command, incoming.command)]
+    KeyPathBlocked {
+        existing: ConflictEntry,
+        incoming: ConflictEntry,
+    },
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    terminal: Option<ConflictEntry>,
+    children: BTreeMap<ChordKey, TrieNode>,
+}
+
+impl TrieNode {
+    fn first_terminal_descendant(&self) -> Option<&ConflictEntry> {
+        for child in self.children.values() {
+            if let Some(entry) = child
+                .terminal
+                .as_ref()
+                .or_else(|| child.first_terminal_descendant())
+            {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+/// A prefix tree over chord sequences, modeled on the keymap trie from the
+/// Trinitrix keymaps crate. A single-chord binding is a path of length one;
+/// an emacs/tmux-style prefix binding like `super + a ; b ; c` is a path of
+/// three chords, with the binding only complete (`terminal`) at the last
+/// node.
+#[derive(Debug, Default)]
+pub struct BindingTrie {
+    root: TrieNode,
+}
+
+impl BindingTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a binding's chord sequence, reporting a [`Conflict`] if it
+    /// exactly collides with an existing sequence, or if either sequence
+    /// would block the other from ever firing.
+    pub fn insert(&mut self, binding: &Binding) -> Result<(), Conflict> {
+        let path: Vec<ChordKey> = binding
+            .definition
+            .chords
+            .iter()
+            .map(ChordKey::from_chord)
+            .collect();
+        let incoming = ConflictEntry::from(binding);
+        Self::insert_path(&mut self.root, &path, incoming)
+    }
+
+    fn insert_path(
+        node: &mut TrieNode,
+        path: &[ChordKey],
+        incoming: ConflictEntry,
+    ) -> Result<(), Conflict> {
+        let Some((head, rest)) = path.split_first() else {
+            if let Some(existing) = &node.terminal {
+                return Err(Conflict::KeyAlreadySet {
+                    existing: existing.clone(),
+                    incoming,
+                });
+            }
+            if let Some(descendant) = node.first_terminal_descendant() {
+                // `incoming` completes its sequence right here, as a strict
+                // prefix of `descendant`'s longer one: `incoming` is the
+                // `Conflict`'s prefix side and `descendant` the blocked one.
+                return Err(Conflict::KeyPathBlocked {
+                    existing: incoming.clone(),
+                    incoming: descendant.clone(),
+                });
+            }
+            node.terminal = Some(incoming);
+            return Ok(());
+        };
+
+        if let Some(existing) = &node.terminal {
+            return Err(Conflict::KeyPathBlocked {
+                existing: existing.clone(),
+                incoming,
+            });
+        }
+        let child = node.children.entry(head.clone()).or_default();
+        Self::insert_path(child, rest, incoming)
+    }
+}
@@ -1,15 +1,45 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
 use sweet::{
     token::{Key, KeyAttribute, Modifier},
-    Binding, Definition, ParseError, SwhkdParser,
+    Binding, Chord, Conflict, Definition, ParseError, ParserInput, Span, SwhkdParser,
 };
 
+/// A config file on disk, deleted on drop. `ParserInput::Path` is only
+/// exercised against real files, so tests that need it write one here rather
+/// than relying on `ParserInput::Raw`.
+struct TempConfig(PathBuf);
+
+impl TempConfig {
+    fn new(contents: &str) -> Self {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "sweet-test-{}-{}.swhkdrc",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, contents).expect("failed to write temp config");
+        Self(path)
+    }
+}
+
+impl Drop for TempConfig {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
 #[test]
 fn test_basic_keybind() -> Result<(), ParseError> {
     let contents = "
 r
     alacritty
             ";
-    SwhkdParser::from(&contents)?;
+    SwhkdParser::from(ParserInput::Raw(contents))?;
     Ok(())
 }
 
@@ -25,38 +55,50 @@ w
 t
     /bin/firefox
         ";
-    let parsed = SwhkdParser::from(&contents)?;
+    let parsed = SwhkdParser::from(ParserInput::Raw(contents))?;
 
     let known = vec![
         Binding {
             definition: Definition {
-                modifiers: vec![],
-                key: Key {
-                    key: "r".to_string(),
-                    attribute: KeyAttribute::None,
-                },
+                chords: vec![Chord {
+                    modifiers: vec![],
+                    key: Key {
+                        key: "r".to_string(),
+                        attribute: KeyAttribute::None,
+                    },
+                }],
+                span: Span::default(),
             },
             command: "alacritty".to_string().to_string(),
+            span: Span::default(),
         },
         Binding {
             definition: Definition {
-                modifiers: vec![],
-                key: Key {
-                    key: "w".to_string(),
-                    attribute: KeyAttribute::None,
-                },
+                chords: vec![Chord {
+                    modifiers: vec![],
+                    key: Key {
+                        key: "w".to_string(),
+                        attribute: KeyAttribute::None,
+                    },
+                }],
+                span: Span::default(),
             },
             command: "kitty".to_string().to_string(),
+            span: Span::default(),
         },
         Binding {
             definition: Definition {
-                modifiers: vec![],
-                key: Key {
-                    key: "t".to_string(),
-                    attribute: KeyAttribute::None,
-                },
+                chords: vec![Chord {
+                    modifiers: vec![],
+                    key: Key {
+                        key: "t".to_string(),
+                        attribute: KeyAttribute::None,
+                    },
+                }],
+                span: Span::default(),
             },
             command: "/bin/firefox".to_string().to_string(),
+            span: Span::default(),
         },
     ];
 
@@ -77,28 +119,36 @@ w
 #t
     #/bin/firefox
         ";
-    let parsed = SwhkdParser::from(&contents)?;
+    let parsed = SwhkdParser::from(ParserInput::Raw(contents))?;
 
     let known = vec![
         Binding {
             definition: Definition {
-                modifiers: vec![],
-                key: Key {
-                    key: "r".to_string(),
-                    attribute: KeyAttribute::None,
-                },
+                chords: vec![Chord {
+                    modifiers: vec![],
+                    key: Key {
+                        key: "r".to_string(),
+                        attribute: KeyAttribute::None,
+                    },
+                }],
+                span: Span::default(),
             },
             command: "alacritty".to_string().to_string(),
+            span: Span::default(),
         },
         Binding {
             definition: Definition {
-                modifiers: vec![],
-                key: Key {
-                    key: "w".to_string(),
-                    attribute: KeyAttribute::None,
-                },
+                chords: vec![Chord {
+                    modifiers: vec![],
+                    key: Key {
+                        key: "w".to_string(),
+                        attribute: KeyAttribute::None,
+                    },
+                }],
+                span: Span::default(),
             },
             command: "kitty".to_string().to_string(),
+            span: Span::default(),
         },
     ];
 
@@ -114,16 +164,20 @@ super + 5
     alacritty
         ";
 
-    let parsed = SwhkdParser::from(&contents)?;
+    let parsed = SwhkdParser::from(ParserInput::Raw(contents))?;
     let known = vec![Binding {
         definition: Definition {
-            modifiers: vec![Modifier("super".to_string())],
-            key: Key {
-                key: "5".to_string(),
-                attribute: KeyAttribute::None,
-            },
+            chords: vec![Chord {
+                modifiers: vec![Modifier("super".to_string())],
+                key: Key {
+                    key: "5".to_string(),
+                    attribute: KeyAttribute::None,
+                },
+            }],
+            span: Span::default(),
         },
         command: "alacritty".to_string().to_string(),
+        span: Span::default(),
     }];
 
     assert_eq!(parsed.bindings, known);
@@ -138,5 +192,139 @@ shift + k + m
     notify-send 'Hello world!'
             ";
 
-    assert!(SwhkdParser::from(&contents).is_err());
-}
\ No newline at end of file
+    assert!(SwhkdParser::from(ParserInput::Raw(contents)).is_err());
+}
+
+#[test]
+fn test_parse_with_errors_recovers_past_bad_declarations() {
+    let contents = "
+r
+    alacritty
+
+super + {1,2}
+    {firefox}
+
+shift + {3,4,5}
+    {foo,bar}
+
+w
+    kitty
+        ";
+
+    let (parsed, errors) = SwhkdParser::parse_with_errors(ParserInput::Raw(contents));
+
+    // Both mismatched-variant-count bindings are recorded as errors...
+    assert_eq!(errors.len(), 2);
+    // ...but parsing keeps going and still recovers the two good bindings.
+    let parsed = parsed.expect("a grammar-valid config should still parse");
+    assert_eq!(
+        parsed
+            .bindings
+            .iter()
+            .map(|binding| binding.command.as_str())
+            .collect::<Vec<_>>(),
+        vec!["alacritty", "kitty"]
+    );
+}
+
+#[test]
+fn test_check_conflicts_reports_key_already_set() -> Result<(), ParseError> {
+    let contents = "
+r
+    alacritty
+
+r
+    kitty
+        ";
+
+    let parsed = SwhkdParser::from(ParserInput::Raw(contents))?;
+    let conflicts = parsed.check_conflicts();
+
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(conflicts[0], Conflict::KeyAlreadySet { .. }));
+
+    Ok(())
+}
+
+#[test]
+fn test_multi_chord_sequence_parses() -> Result<(), ParseError> {
+    let contents = "
+super + a ; b
+    notify-send seq
+        ";
+
+    let parsed = SwhkdParser::from(ParserInput::Raw(contents))?;
+
+    assert_eq!(parsed.bindings.len(), 1);
+    let chords = &parsed.bindings[0].definition.chords;
+    assert_eq!(
+        chords,
+        &vec![
+            Chord {
+                modifiers: vec![Modifier("super".to_string())],
+                key: Key {
+                    key: "a".to_string(),
+                    attribute: KeyAttribute::None,
+                },
+            },
+            Chord {
+                modifiers: vec![],
+                key: Key {
+                    key: "b".to_string(),
+                    attribute: KeyAttribute::None,
+                },
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_check_conflicts_reports_key_path_blocked() -> Result<(), ParseError> {
+    let contents = "
+super + a
+    alacritty
+
+super + a ; b
+    kitty
+        ";
+
+    let parsed = SwhkdParser::from(ParserInput::Raw(contents))?;
+    let conflicts = parsed.check_conflicts();
+
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(conflicts[0], Conflict::KeyPathBlocked { .. }));
+
+    Ok(())
+}
+
+#[test]
+fn test_parses_from_real_file_path() -> Result<(), ParseError> {
+    let config = TempConfig::new(
+        "
+r
+    alacritty
+        ",
+    );
+
+    let parsed = SwhkdParser::from(ParserInput::Path(&config.0))?;
+    let known = vec![Binding {
+        definition: Definition {
+            chords: vec![Chord {
+                modifiers: vec![],
+                key: Key {
+                    key: "r".to_string(),
+                    attribute: KeyAttribute::None,
+                },
+            }],
+            span: Span::default(),
+        },
+        command: "alacritty".to_string(),
+        span: Span::default(),
+    }];
+
+    assert_eq!(parsed.bindings, known);
+
+    Ok(())
+}